@@ -2,46 +2,54 @@ use std::fs::File;
 use std::io::BufReader;
 
 use csv::Trim;
-use libpayments::client::Client;
-use libpayments::transaction::Transaction;
-use std::collections::HashMap;
+use libpayments::ledger::Ledger;
+use libpayments::transaction::{Transaction, TransactionRecord};
 use std::error::Error;
 use std::io;
 
-fn process_records(filename: &str) -> Result<HashMap<u16, Client>, Box<dyn Error>> {
+mod server;
+
+fn process_records(filename: &str) -> Result<Ledger, Box<dyn Error>> {
     let f = File::open(filename)?;
     let file_reader = BufReader::new(f);
-    let mut clients = HashMap::<u16, Client>::new();
+    let mut ledger = Ledger::new();
     let mut reader = csv::ReaderBuilder::new()
         .trim(Trim::All)
         .from_reader(file_reader);
     for result in reader.deserialize() {
-        let record: Transaction = result?;
-        match clients.get_mut(&record.client) {
-            Some(client) => {
-                client.transactions.push(record);
+        let record: TransactionRecord = match result {
+            Ok(record) => record,
+            Err(err) => {
+                eprintln!("skipping malformed record: {err}");
+                continue;
             }
-            None => {
-                clients.insert(
-                    record.client,
-                    Client::new(record.client).with_transactions(vec![record]),
-                );
+        };
+        let tx: Transaction = match record.try_into() {
+            Ok(tx) => tx,
+            Err(err) => {
+                eprintln!("skipping invalid record: {err}");
+                continue;
             }
+        };
+        if let Err(err) = ledger.process(tx) {
+            eprintln!("skipping tx {}: {err}", tx.tx);
         }
     }
-    Ok(clients)
+    Ok(ledger)
 }
 
-fn output_record_states(clients: &HashMap<u16, Client>) -> Result<(), Box<dyn Error>> {
-    let mut writer = csv::Writer::from_writer(io::stdout());
-    for c in clients.values() {
-        writer.serialize(c.calculate_state())?;
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let first = args.next().expect("No path or subcommand given");
+
+    if first == "serve" {
+        let addr = args.next().unwrap_or_else(|| "127.0.0.1:7878".to_string());
+        server::run(&addr).expect("Server failed");
+        return;
     }
-    Ok(writer.flush()?)
-}
 
-fn main() {
-    let path = std::env::args().nth(1).expect("No path given");
-    let clients = process_records(&path).expect("Failed to parse client records");
-    output_record_states(&clients).expect("Failed to output client states");
+    let ledger = process_records(&first).expect("Failed to parse transaction records");
+    ledger
+        .dump_csv(io::stdout())
+        .expect("Failed to output client states");
 }
@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use libpayments::ledger::Ledger;
+use libpayments::transaction::{Transaction, TransactionRecord};
+
+/// Runs the payments engine as a long-running TCP server instead of a one-shot CLI.
+///
+/// Each connection is a line-oriented stream that may interleave CSV transaction rows
+/// (`type,client,tx,amount`) with account-state queries (`STATE,<client>`).
+/// Transactions from every connection are applied to one `Ledger` shared behind a
+/// mutex, so concurrent connections can't corrupt balances.
+pub fn run(addr: &str) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(addr)?;
+    let ledger = Arc::new(Mutex::new(Ledger::new()));
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let ledger = Arc::clone(&ledger);
+        thread::spawn(move || {
+            if let Err(err) = handle_connection(stream, &ledger) {
+                eprintln!("connection error: {err}");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, ledger: &Arc<Mutex<Ledger>>) -> Result<(), Box<dyn Error>> {
+    let mut writer = stream.try_clone()?;
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(client) = line.strip_prefix("STATE,") {
+            let client: u16 = client.trim().parse()?;
+            let state = ledger.lock().unwrap().state_for(client);
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                state.id, state.available, state.held, state.total, state.locked
+            )?;
+            continue;
+        }
+
+        let result = parse_record(line)
+            .and_then(|tx| ledger.lock().unwrap().process(tx).map_err(Into::into));
+        match result {
+            Ok(()) => writeln!(writer, "OK")?,
+            Err(err) => writeln!(writer, "ERROR {err}")?,
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_record(line: &str) -> Result<Transaction, Box<dyn Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(csv::Trim::All)
+        .from_reader(line.as_bytes());
+    let record: TransactionRecord = reader
+        .deserialize()
+        .next()
+        .ok_or("empty record")??;
+    Ok(Transaction::try_from(record)?)
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::ops::{Add, Neg, Sub};
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+// Four decimal places is enough precision for the ledger. Amounts are stored as an
+// integer scaled by this factor instead of as a binary float that would drift under
+// repeated arithmetic. The backing integer is i128 (with checked arithmetic on top)
+// so that even large or repeated deposits can't silently wrap a balance.
+const SCALE: i128 = 10_000;
+
+/// A fixed-point monetary amount with four decimal places of precision.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i128);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+}
+
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, rhs: Amount) -> Amount {
+        Amount(
+            self.0
+                .checked_add(rhs.0)
+                .expect("amount overflowed during addition"),
+        )
+    }
+}
+
+impl Sub for Amount {
+    type Output = Amount;
+
+    fn sub(self, rhs: Amount) -> Amount {
+        Amount(
+            self.0
+                .checked_sub(rhs.0)
+                .expect("amount overflowed during subtraction"),
+        )
+    }
+}
+
+impl Neg for Amount {
+    type Output = Amount;
+
+    fn neg(self) -> Amount {
+        Amount(
+            self.0
+                .checked_neg()
+                .expect("amount overflowed during negation"),
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseAmountError(String);
+
+impl fmt::Display for ParseAmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid amount: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseAmountError {}
+
+impl FromStr for Amount {
+    type Err = ParseAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let (negative, unsigned) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed),
+        };
+
+        let mut parts = unsigned.splitn(2, '.');
+        let whole = parts.next().unwrap_or("");
+        let frac = parts.next().unwrap_or("");
+        if frac.len() > 4 {
+            return Err(ParseAmountError(trimmed.to_string()));
+        }
+
+        let whole: i128 = whole
+            .parse()
+            .map_err(|_| ParseAmountError(trimmed.to_string()))?;
+        let mut frac_digits = frac.to_string();
+        frac_digits.push_str(&"0".repeat(4 - frac_digits.len()));
+        let frac: i128 = frac_digits
+            .parse()
+            .map_err(|_| ParseAmountError(trimmed.to_string()))?;
+
+        let scaled = whole
+            .checked_mul(SCALE)
+            .and_then(|whole| whole.checked_add(frac))
+            .ok_or_else(|| ParseAmountError(trimmed.to_string()))?;
+        Ok(Amount(if negative { -scaled } else { scaled }))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let whole = abs / SCALE.unsigned_abs();
+        let frac = abs % SCALE.unsigned_abs();
+        if frac == 0 {
+            write!(f, "{sign}{whole}")
+        } else {
+            let mut frac_str = format!("{frac:04}");
+            while frac_str.ends_with('0') {
+                frac_str.pop();
+            }
+            write!(f, "{sign}{whole}.{frac_str}")
+        }
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_displays_whole_numbers() {
+        let amount: Amount = "5".parse().unwrap();
+        assert_eq!(amount.to_string(), "5");
+    }
+
+    #[test]
+    fn trims_trailing_zeros() {
+        let amount: Amount = "1.5000".parse().unwrap();
+        assert_eq!(amount.to_string(), "1.5");
+
+        let amount: Amount = "2.7420".parse().unwrap();
+        assert_eq!(amount.to_string(), "2.742");
+    }
+
+    #[test]
+    fn parses_and_displays_negative_numbers() {
+        let amount: Amount = "-3.25".parse().unwrap();
+        assert_eq!(amount.to_string(), "-3.25");
+    }
+
+    #[test]
+    fn rejects_too_much_precision() {
+        assert!("1.23456".parse::<Amount>().is_err());
+    }
+
+    #[test]
+    fn add_sub_neg_are_exact() {
+        let a: Amount = "1.0001".parse().unwrap();
+        let b: Amount = "0.0001".parse().unwrap();
+        assert_eq!((a - b).to_string(), "1");
+        assert_eq!((a + b).to_string(), "1.0002");
+        assert_eq!((-a).to_string(), "-1.0001");
+    }
+
+    #[test]
+    #[should_panic(expected = "overflowed")]
+    fn add_panics_instead_of_wrapping_on_overflow() {
+        let max: Amount = Amount(i128::MAX);
+        let _ = max + Amount(1);
+    }
+}
@@ -0,0 +1,580 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+
+use crate::amount::Amount;
+use crate::client::ClientState;
+use crate::transaction::{Transaction, TransactionType};
+
+/// Lifecycle of a single deposit/withdrawal as tracked by the ledger, so that a
+/// dispute/resolve/chargeback can be validated against it without re-scanning the
+/// transaction history.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TxState {
+    Processed,
+    Disputed,
+    Resolved,
+    ChargedBack,
+}
+
+/// Reasons a transaction can be rejected by the ledger.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LedgerError {
+    /// A withdrawal would take `available` below zero.
+    NotEnoughFunds,
+    /// A dispute, resolve or chargeback referenced a `tx` the ledger has no record of.
+    UnknownTx,
+    /// A dispute was raised against a transaction that is already disputed (or has
+    /// already been through a dispute/resolve/chargeback cycle).
+    AlreadyDisputed,
+    /// A resolve or chargeback was raised against a transaction that isn't currently
+    /// disputed.
+    NotDisputed,
+    /// The account has been locked by a prior chargeback and accepts no more
+    /// transactions.
+    FrozenAccount,
+    /// A deposit or withdrawal reused the `tx` id of a deposit or withdrawal already
+    /// processed for the same client. Ids are tracked per client, so the same `tx` id
+    /// under a different client is not rejected.
+    DuplicateTx,
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::NotEnoughFunds => write!(f, "not enough available funds"),
+            LedgerError::UnknownTx => write!(f, "unknown tx"),
+            LedgerError::AlreadyDisputed => write!(f, "tx is already disputed"),
+            LedgerError::NotDisputed => write!(f, "tx is not under dispute"),
+            LedgerError::FrozenAccount => write!(f, "account is frozen"),
+            LedgerError::DuplicateTx => write!(f, "tx id already seen"),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
+
+/// Processes transactions one at a time, maintaining just enough state to apply and
+/// validate each record in O(1) instead of re-scanning the full transaction log for
+/// every dispute, resolve or chargeback.
+#[derive(Debug, Default)]
+pub struct Ledger {
+    accounts: HashMap<u16, ClientState>,
+    // The amount is stored as its *effect on `available`* at the time the deposit or
+    // withdrawal was processed (positive for a deposit, negative for a withdrawal).
+    // That lets dispute/resolve/chargeback apply the same formula regardless of which
+    // kind of transaction is being disputed.
+    transaction_amounts: HashMap<(u16, u32), Amount>,
+    // Also doubles as the set of (client, tx) ids already seen, so a replayed
+    // deposit/withdrawal id can be rejected as `DuplicateTx` instead of silently
+    // corrupting the dispute-reference lookups above.
+    transaction_state: HashMap<(u16, u32), TxState>,
+}
+
+impl Ledger {
+    pub fn new() -> Ledger {
+        Ledger::default()
+    }
+
+    pub fn state_for(&self, client: u16) -> ClientState {
+        self.accounts
+            .get(&client)
+            .copied()
+            .unwrap_or_else(|| ClientState::new(client))
+    }
+
+    pub fn accounts(&self) -> &HashMap<u16, ClientState> {
+        &self.accounts
+    }
+
+    /// Serializes every account's current state as CSV, ordered by client id with an
+    /// explicit `client,available,held,total,locked` header so that output is
+    /// reproducible across runs and diffable against a golden file. Since the ledger
+    /// only ever keeps per-account aggregates and the dispute-reference maps in
+    /// memory, this can run after streaming a transaction log far larger than memory
+    /// through `process`.
+    pub fn dump_csv<W: io::Write>(&self, writer: W) -> Result<(), csv::Error> {
+        let mut writer = csv::Writer::from_writer(writer);
+        let sorted: BTreeMap<u16, ClientState> =
+            self.accounts.iter().map(|(&id, &state)| (id, state)).collect();
+        for state in sorted.values() {
+            writer.serialize(state)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn process(&mut self, tx: Transaction) -> Result<(), LedgerError> {
+        let account = self
+            .accounts
+            .entry(tx.client)
+            .or_insert_with(|| ClientState::new(tx.client));
+        // Assuming that a locked account should not accept any additional transactions
+        if account.locked {
+            return Err(LedgerError::FrozenAccount);
+        }
+
+        match tx.r#type {
+            TransactionType::Deposit => self.process_deposit(tx),
+            TransactionType::Withdrawal => self.process_withdrawal(tx),
+            TransactionType::Dispute => self.process_dispute(tx),
+            TransactionType::Resolve => self.process_resolve(tx),
+            TransactionType::Chargeback => self.process_chargeback(tx),
+        }
+    }
+
+    fn process_deposit(&mut self, tx: Transaction) -> Result<(), LedgerError> {
+        let Some(amount) = tx.amount else { return Ok(()) };
+        if self.transaction_state.contains_key(&(tx.client, tx.tx)) {
+            return Err(LedgerError::DuplicateTx);
+        }
+        let account = self.accounts.get_mut(&tx.client).unwrap();
+        account.available = account.available + amount;
+        account.total = account.total + amount;
+        self.record_processed(tx, amount);
+        Ok(())
+    }
+
+    fn process_withdrawal(&mut self, tx: Transaction) -> Result<(), LedgerError> {
+        let Some(amount) = tx.amount else { return Ok(()) };
+        if self.transaction_state.contains_key(&(tx.client, tx.tx)) {
+            return Err(LedgerError::DuplicateTx);
+        }
+        let account = self.accounts.get_mut(&tx.client).unwrap();
+        if account.available < amount {
+            return Err(LedgerError::NotEnoughFunds);
+        }
+        account.available = account.available - amount;
+        account.total = account.total - amount;
+        self.record_processed(tx, -amount);
+        Ok(())
+    }
+
+    fn record_processed(&mut self, tx: Transaction, effect: Amount) {
+        let key = (tx.client, tx.tx);
+        self.transaction_amounts.insert(key, effect);
+        self.transaction_state.insert(key, TxState::Processed);
+    }
+
+    fn process_dispute(&mut self, tx: Transaction) -> Result<(), LedgerError> {
+        let key = (tx.client, tx.tx);
+        match self.transaction_state.get(&key) {
+            None => return Err(LedgerError::UnknownTx),
+            Some(TxState::Processed) => {}
+            Some(_) => return Err(LedgerError::AlreadyDisputed),
+        }
+        let effect = self.transaction_amounts[&key];
+        let account = self.accounts.get_mut(&tx.client).unwrap();
+        account.available = account.available - effect;
+        account.held = account.held + effect;
+        self.transaction_state.insert(key, TxState::Disputed);
+        Ok(())
+    }
+
+    fn process_resolve(&mut self, tx: Transaction) -> Result<(), LedgerError> {
+        let key = (tx.client, tx.tx);
+        match self.transaction_state.get(&key) {
+            None => return Err(LedgerError::UnknownTx),
+            Some(TxState::Disputed) => {}
+            Some(_) => return Err(LedgerError::NotDisputed),
+        }
+        let effect = self.transaction_amounts[&key];
+        let account = self.accounts.get_mut(&tx.client).unwrap();
+        account.held = account.held - effect;
+        account.available = account.available + effect;
+        self.transaction_state.insert(key, TxState::Resolved);
+        Ok(())
+    }
+
+    fn process_chargeback(&mut self, tx: Transaction) -> Result<(), LedgerError> {
+        let key = (tx.client, tx.tx);
+        match self.transaction_state.get(&key) {
+            None => return Err(LedgerError::UnknownTx),
+            Some(TxState::Disputed) => {}
+            Some(_) => return Err(LedgerError::NotDisputed),
+        }
+        let effect = self.transaction_amounts[&key];
+        let account = self.accounts.get_mut(&tx.client).unwrap();
+        account.held = account.held - effect;
+        account.total = account.total - effect;
+        account.locked = true;
+        self.transaction_state.insert(key, TxState::ChargedBack);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deposit(tx: u32, amount: &str) -> Transaction {
+        Transaction {
+            r#type: TransactionType::Deposit,
+            client: 0,
+            tx,
+            amount: Some(amount.parse().unwrap()),
+        }
+    }
+
+    fn withdrawal(tx: u32, amount: &str) -> Transaction {
+        Transaction {
+            r#type: TransactionType::Withdrawal,
+            client: 0,
+            tx,
+            amount: Some(amount.parse().unwrap()),
+        }
+    }
+
+    fn dispute(tx: u32) -> Transaction {
+        Transaction {
+            r#type: TransactionType::Dispute,
+            client: 0,
+            tx,
+            amount: None,
+        }
+    }
+
+    fn resolve(tx: u32) -> Transaction {
+        Transaction {
+            r#type: TransactionType::Resolve,
+            client: 0,
+            tx,
+            amount: None,
+        }
+    }
+
+    fn chargeback(tx: u32) -> Transaction {
+        Transaction {
+            r#type: TransactionType::Chargeback,
+            client: 0,
+            tx,
+            amount: None,
+        }
+    }
+
+    fn amt(s: &str) -> Amount {
+        s.parse().unwrap()
+    }
+
+    fn run(transactions: Vec<Transaction>) -> ClientState {
+        let mut ledger = Ledger::new();
+        for tx in transactions {
+            let _ = ledger.process(tx);
+        }
+        ledger.state_for(0)
+    }
+
+    #[test]
+    fn test_deposit_withdrawl_ok() {
+        let state = run(vec![deposit(1, "5.0"), withdrawal(2, "3.5")]);
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("1.5"),
+            total: amt("1.5"),
+            held: amt("0.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_deposit_dispute_ok() {
+        let state = run(vec![deposit(1, "10.0"), dispute(1)]);
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("0.0"),
+            total: amt("10.0"),
+            held: amt("10.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_deposit_dispute_no_match() {
+        let state = run(vec![deposit(1, "10.0"), dispute(2)]);
+
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("10.0"),
+            total: amt("10.0"),
+            held: amt("0.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_deposit_dispute_resolve_ok() {
+        let state = run(vec![deposit(1, "10.0"), dispute(1), resolve(1)]);
+
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("10.0"),
+            total: amt("10.0"),
+            held: amt("0.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_deposit_dispute_chargeback_ok() {
+        let state = run(vec![deposit(1, "10.0"), dispute(1), chargeback(1)]);
+
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("0.0"),
+            total: amt("0.0"),
+            held: amt("0.0"),
+            locked: true,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_withdraw_dispute_ok() {
+        let state = run(vec![deposit(1, "10.0"), withdrawal(2, "5.0"), dispute(2)]);
+
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("10.0"),
+            total: amt("5.0"),
+            held: amt("-5.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_withdraw_dispute_resolve_ok() {
+        let state = run(vec![
+            deposit(1, "10.0"),
+            withdrawal(2, "5.0"),
+            dispute(2),
+            resolve(2),
+        ]);
+
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("5.0"),
+            total: amt("5.0"),
+            held: amt("0.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_withdraw_dispute_chargeback_ok() {
+        let state = run(vec![
+            deposit(1, "10.0"),
+            withdrawal(2, "5.0"),
+            dispute(2),
+            chargeback(2),
+        ]);
+
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("10.0"),
+            total: amt("10.0"),
+            held: amt("0.0"),
+            locked: true,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_ignore_dispute_before_transaction() {
+        let state = run(vec![dispute(1), deposit(1, "15.0")]);
+
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("15.0"),
+            total: amt("15.0"),
+            held: amt("0.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_ignore_resolve_before_dispute() {
+        let state = run(vec![deposit(1, "10.0"), resolve(1), dispute(1)]);
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("0.0"),
+            total: amt("10.0"),
+            held: amt("10.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_ignore_chargeback_before_dispute() {
+        let state = run(vec![deposit(1, "10.0"), chargeback(1), dispute(1)]);
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("0.0"),
+            total: amt("10.0"),
+            held: amt("10.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_ignore_resolve_after_chargeback() {
+        let state = run(vec![
+            deposit(1, "10.0"),
+            dispute(1),
+            chargeback(1),
+            resolve(1),
+        ]);
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("0.0"),
+            total: amt("0.0"),
+            held: amt("0.0"),
+            locked: true,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_ignore_chargeback_after_resolve() {
+        let state = run(vec![
+            deposit(1, "10.0"),
+            dispute(1),
+            resolve(1),
+            chargeback(1),
+        ]);
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("10.0"),
+            total: amt("10.0"),
+            held: amt("0.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_ignore_after_chargeback() {
+        let state = run(vec![
+            deposit(1, "10.0"),
+            withdrawal(2, "5.0"),
+            dispute(2),
+            chargeback(2),
+            deposit(3, "20.0"),
+            withdrawal(4, "1.5"),
+        ]);
+
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("10.0"),
+            total: amt("10.0"),
+            held: amt("0.0"),
+            locked: true,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn test_redispute_after_resolve_is_rejected() {
+        // Once a dispute has been resolved, the same tx id cannot be disputed again.
+        let state = run(vec![deposit(1, "10.0"), dispute(1), resolve(1), dispute(1)]);
+        let expected_state = ClientState {
+            id: 0,
+            available: amt("10.0"),
+            total: amt("10.0"),
+            held: amt("0.0"),
+            locked: false,
+        };
+        assert_eq!(state, expected_state);
+    }
+
+    #[test]
+    fn withdrawal_over_available_is_rejected() {
+        let mut ledger = Ledger::new();
+        ledger.process(deposit(1, "10.0")).unwrap();
+        let result = ledger.process(withdrawal(2, "20.0"));
+        assert_eq!(result, Err(LedgerError::NotEnoughFunds));
+    }
+
+    #[test]
+    fn dispute_of_unknown_tx_is_rejected() {
+        let mut ledger = Ledger::new();
+        let result = ledger.process(dispute(1));
+        assert_eq!(result, Err(LedgerError::UnknownTx));
+    }
+
+    #[test]
+    fn resolve_without_dispute_is_rejected() {
+        let mut ledger = Ledger::new();
+        ledger.process(deposit(1, "10.0")).unwrap();
+        let result = ledger.process(resolve(1));
+        assert_eq!(result, Err(LedgerError::NotDisputed));
+    }
+
+    #[test]
+    fn redispute_of_disputed_tx_is_rejected() {
+        let mut ledger = Ledger::new();
+        ledger.process(deposit(1, "10.0")).unwrap();
+        ledger.process(dispute(1)).unwrap();
+        let result = ledger.process(dispute(1));
+        assert_eq!(result, Err(LedgerError::AlreadyDisputed));
+    }
+
+    #[test]
+    fn transactions_after_chargeback_are_rejected() {
+        let mut ledger = Ledger::new();
+        ledger.process(deposit(1, "10.0")).unwrap();
+        ledger.process(dispute(1)).unwrap();
+        ledger.process(chargeback(1)).unwrap();
+        let result = ledger.process(deposit(2, "5.0"));
+        assert_eq!(result, Err(LedgerError::FrozenAccount));
+    }
+
+    #[test]
+    fn duplicate_deposit_tx_is_rejected() {
+        let mut ledger = Ledger::new();
+        ledger.process(deposit(1, "10.0")).unwrap();
+        let result = ledger.process(deposit(1, "5.0"));
+        assert_eq!(result, Err(LedgerError::DuplicateTx));
+    }
+
+    #[test]
+    fn duplicate_withdrawal_tx_is_rejected() {
+        let mut ledger = Ledger::new();
+        ledger.process(deposit(1, "10.0")).unwrap();
+        ledger.process(withdrawal(2, "1.0")).unwrap();
+        let result = ledger.process(withdrawal(2, "1.0"));
+        assert_eq!(result, Err(LedgerError::DuplicateTx));
+    }
+
+    #[test]
+    fn dump_csv_is_sorted_by_client_with_explicit_header() {
+        let mut ledger = Ledger::new();
+        for client in [3u16, 1, 2] {
+            ledger
+                .process(Transaction {
+                    r#type: TransactionType::Deposit,
+                    client,
+                    tx: u32::from(client),
+                    amount: Some(amt("1.0")),
+                })
+                .unwrap();
+        }
+
+        let mut output = Vec::new();
+        ledger.dump_csv(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("client,available,held,total,locked"));
+        let client_column = |line: &str| line.split(',').next().unwrap().to_string();
+        let rows: Vec<String> = lines.map(client_column).collect();
+        assert_eq!(rows, vec!["1", "2", "3"]);
+    }
+}
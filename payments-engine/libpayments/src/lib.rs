@@ -0,0 +1,4 @@
+pub mod amount;
+pub mod client;
+pub mod ledger;
+pub mod transaction;